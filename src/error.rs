@@ -0,0 +1,36 @@
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+
+/// The error returned by [`try_with`] when there's no value currently in scope for the given
+/// key.
+///
+/// [`try_with`]: fn.try_with.html
+#[derive(Debug)]
+pub struct NotInScope {
+    type_name: &'static str,
+}
+
+impl NotInScope {
+    #[inline]
+    pub(crate) fn new<T>() -> Self
+    where
+        T: ?Sized,
+    {
+        Self {
+            type_name: type_name::<T>(),
+        }
+    }
+}
+
+impl fmt::Display for NotInScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "not in scope: no value of type `{}` is currently in scope on this thread",
+            self.type_name
+        )
+    }
+}
+
+impl Error for NotInScope {}
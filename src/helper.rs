@@ -1,4 +1,4 @@
-use crate::Scope;
+use crate::{NotInScope, Scope};
 use std::thread::LocalKey;
 
 /// Execute a function scoped with given value reference.
@@ -29,3 +29,112 @@ where
 {
     key.with(|scope| scope.with(fun))
 }
+
+/// Execute a function scoped with given, exclusive, value reference.
+///
+/// This is the exclusive-access counterpart to [`scoped`]: it allows a `&mut T` to be handed
+/// out (via [`with_mut`]) to code called from within `fun`.
+///
+/// [`scoped`]: fn.scoped.html
+/// [`with_mut`]: fn.with_mut.html
+#[inline]
+pub fn scoped_mut<T, TFn, TRet>(key: &'static LocalKey<Scope<T>>, value: &mut T, fun: TFn) -> TRet
+where
+    TFn: FnOnce() -> TRet,
+    T: ?Sized,
+{
+    key.with(|scope| scope.scoped_mut(value, fun))
+}
+
+/// Gets an exclusive reference to the value from the current scope. Given function will
+/// receive `None` if this is not called within a [`scoped_mut`] scope, or if a `&mut T` from
+/// this scope is already handed out.
+///
+/// [`scoped_mut`]: fn.scoped_mut.html
+#[inline]
+pub fn with_mut<T, TFn, TRet>(key: &'static LocalKey<Scope<T>>, fun: TFn) -> TRet
+where
+    TFn: FnOnce(Option<&mut T>) -> TRet,
+    T: ?Sized,
+{
+    key.with(|scope| scope.with_mut(fun))
+}
+
+/// Runs the given function with every reference currently in scope on this thread, innermost
+/// (most recently entered) first, outermost last. Yields nothing if called outside of any
+/// scope.
+///
+/// Useful for layered contexts, where an inner scope augments rather than replaces an outer
+/// one: a stack of fallback providers, say, where you want to look at every provider currently
+/// in scope instead of just the innermost one.
+///
+/// Like [`with`], the stack is only borrowed to snapshot what's currently on it; the borrow is
+/// released before `fun` runs, so `fun` may freely establish a new scope for this key (`scoped`
+/// or `scoped_many!`), or call `with_all` again, without it panicking.
+///
+/// See [`thread_scoped_ref`] for an example of nested scopes.
+///
+/// [`with`]: fn.with.html
+/// [`thread_scoped_ref`]: macro.thread_scoped_ref.html
+#[inline]
+pub fn with_all<T, TFn, TRet>(key: &'static LocalKey<Scope<T>>, fun: TFn) -> TRet
+where
+    TFn: FnOnce(&mut dyn Iterator<Item = &T>) -> TRet,
+    T: ?Sized,
+{
+    key.with(|scope| scope.with_all(fun))
+}
+
+/// Returns `true` if there's currently a value in scope for this key on this thread via
+/// [`scoped`].
+///
+/// This mirrors [`with`]/[`try_with`]/[`with_all`] and only looks at the shared stack: a
+/// [`scoped_mut`] scope does not make this return `true`, the same way it doesn't make
+/// `with`/`try_with`/`with_all` see a value. Use [`with_mut`] to check for an exclusive scope
+/// instead.
+///
+/// [`scoped`]: fn.scoped.html
+/// [`with`]: fn.with.html
+/// [`try_with`]: fn.try_with.html
+/// [`with_all`]: fn.with_all.html
+/// [`scoped_mut`]: fn.scoped_mut.html
+/// [`with_mut`]: fn.with_mut.html
+#[inline]
+pub fn is_set<T>(key: &'static LocalKey<Scope<T>>) -> bool
+where
+    T: ?Sized,
+{
+    key.with(|scope| scope.is_set())
+}
+
+/// Like [`with`], but runs the function only if there's a value currently in scope, and returns
+/// a [`NotInScope`] error instead of calling it with `None`.
+///
+/// This avoids the `if let Some(value) = maybe_value { ... } else { ... }` boilerplate needed
+/// with [`with`] when the "not in scope" case is itself an error, e.g. when the caller wants to
+/// turn a missing scope into an error of its own (see the Serde demo test for a worked example
+/// with `de::Error::custom`):
+///
+/// ```
+/// use thread_scoped_ref::{scoped, thread_scoped_ref, try_with};
+///
+/// thread_scoped_ref!(CURRENT_ID, u64);
+///
+/// let id = 42;
+/// let result = scoped(&CURRENT_ID, &id, || try_with(&CURRENT_ID, |id| *id));
+/// assert_eq!(42, result.unwrap());
+///
+/// // outside of any scope, `try_with` fails instead of running the closure with `None`.
+/// assert!(try_with(&CURRENT_ID, |id: &u64| *id).is_err());
+/// ```
+///
+/// [`with`]: fn.with.html
+/// [`NotInScope`]: struct.NotInScope.html
+#[inline]
+pub fn try_with<T, TFn, TRet>(key: &'static LocalKey<Scope<T>>, fun: TFn) -> Result<TRet, NotInScope>
+where
+    TFn: FnOnce(&T) -> TRet,
+    T: ?Sized,
+{
+    key.with(|scope| scope.try_with(fun))
+}
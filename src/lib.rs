@@ -104,10 +104,24 @@
 //!
 //! Something like this can be achieved with thread scoped references. See the Serde demo
 //! test for details.
+//!
+//! # Why is there no RAII guard?
+//!
+//! The scope is always established by wrapping the code that needs it in a closure (`scoped`,
+//! `scoped_mut`, `scoped_many!`), never by a guard value you keep alive by binding it to a
+//! variable. That's deliberate, not an oversight: a guard's `Drop` impl is what would remove the
+//! pointer from the thread-local stack again, but `Drop` isn't guaranteed to run in safe Rust
+//! (`std::mem::forget`, a reference cycle, ...) - forgetting the guard would leave a dangling
+//! pointer on the stack for `with`/`with_all`/`try_with` to dereference later. That's exactly the
+//! unsoundness that got `std::thread::scoped` removed pre-1.0. The closure-based API has no such
+//! gap, since the borrow can't outlive the call that established it, so it's the only entry point
+//! offered here.
 
+mod error;
 mod helper;
 mod scope;
 
+pub use error::NotInScope;
 pub use helper::*;
 pub use scope::Scope;
 
@@ -178,3 +192,46 @@ macro_rules! thread_scoped_ref {
         }
     };
 }
+
+/// Establishes several thread scoped references at once, as a single logical "enter this
+/// context" operation.
+///
+/// Expands to nested [`scoped`] calls, one per `(key, value)` pair, so every key is live for the
+/// duration of the body and all of them are restored (even on panic) in reverse order, without
+/// having to hand-write the nesting yourself.
+///
+/// # Examples
+///
+/// ```
+/// use thread_scoped_ref::{scoped_many, thread_scoped_ref, with};
+/// use std::collections::HashMap;
+///
+/// thread_scoped_ref!(CONFIG, HashMap<String, String>);
+/// thread_scoped_ref!(USER_NAME, str);
+///
+/// let config = HashMap::default();
+/// let user_name = "Ada".to_string();
+///
+/// scoped_many!((&CONFIG, &config), (&USER_NAME, &user_name); || {
+///   with(&CONFIG, |maybe_config| assert!(maybe_config.is_some()));
+///   with(&USER_NAME, |maybe_user_name| {
+///     assert_eq!("Ada", maybe_user_name.unwrap());
+///   });
+/// });
+/// ```
+///
+/// [`scoped`]: fn.scoped.html
+#[macro_export]
+macro_rules! scoped_many {
+    ($(($key:expr, $value:expr)),+ $(,)? ; $body:expr) => {
+        $crate::scoped_many!(@expand [$(($key, $value)),+] $body)
+    };
+    (@expand [($key:expr, $value:expr)] $body:expr) => {
+        $crate::scoped($key, $value, $body)
+    };
+    (@expand [($key:expr, $value:expr), $(($rest_key:expr, $rest_value:expr)),+] $body:expr) => {
+        $crate::scoped($key, $value, || {
+            $crate::scoped_many!(@expand [$(($rest_key, $rest_value)),+] $body)
+        })
+    };
+}
@@ -1,10 +1,10 @@
-use std::cell::RefCell;
-use std::ops::Deref;
+use crate::NotInScope;
+use std::cell::{Cell, RefCell};
 
 /// A scope. There's usually one scope per thread.
 ///
 /// Note: Usually you don't use this directly. See [`thread_scoped_ref`] (with example)
-/// and [`with`] / [`scoped`].
+/// and [`with`] / [`scoped`] / [`with_mut`] / [`scoped_mut`] / [`with_all`] / [`try_with`].
 ///
 /// # Safety
 ///
@@ -13,10 +13,23 @@ use std::ops::Deref;
 /// is removed when the scope function ends. To make sure the reference is removed even in the
 /// case of a panic, there's a cleanup struct that performs cleanup when dropped.
 ///
+/// Shared (`scoped`/`with`/`with_all`) references are kept on a stack, innermost (most recently
+/// entered) last: a nested `scoped` call doesn't replace the outer value, it's pushed on top of
+/// it, and popped again once the nested call returns (or panics). `with` only looks at the top
+/// of the stack, while `with_all` walks the whole stack, innermost to outermost.
+///
+/// Exclusive (`scoped_mut`/`with_mut`) access uses a separate, single-slot storage. An internal
+/// flag tracks whether a `&mut T` from that slot is currently handed out; while it's set, a
+/// re-entrant `with_mut` returns `None` instead of materializing a second, aliasing reference.
+///
 /// [`thread_scoped_ref`]: macro.thread_scoped_ref.html
 /// [`with`]: fn.with.html
 /// [`scoped`]: fn.scoped.html
-pub struct Scope<T>(RefCell<Option<*const T>>)
+/// [`with_mut`]: fn.with_mut.html
+/// [`scoped_mut`]: fn.scoped_mut.html
+/// [`with_all`]: fn.with_all.html
+/// [`try_with`]: fn.try_with.html
+pub struct Scope<T>(RefCell<Vec<*const T>>, RefCell<Option<*mut T>>, Cell<bool>)
 where
     T: ?Sized;
 
@@ -26,7 +39,7 @@ where
     T: ?Sized,
 {
     fn default() -> Self {
-        Self(RefCell::new(None))
+        Self(RefCell::new(Vec::new()), RefCell::new(None), Cell::new(false))
     }
 }
 
@@ -36,7 +49,13 @@ where
 {
     /// Run the given function scoped with given value reference.
     ///
+    /// Scopes can be nested: a nested `scoped` call doesn't hide the outer value for good, it
+    /// just becomes the topmost entry on this thread's stack for `with` until the nested call
+    /// returns; use [`with_all`] to see every entry on the stack, not just the topmost one.
+    ///
     /// Note: Panicking within the function should be ok, the scope is cleaned up properly.
+    ///
+    /// [`with_all`]: #method.with_all
     #[inline]
     pub fn scoped<TFn, TRet>(&self, value: &T, fun: TFn) -> TRet
     where
@@ -45,53 +64,165 @@ where
         // make sure we always remove the value (even when panicking).
         let mut cleanup_on_drop = CleanupOnDrop {
             scope: Some(self),
-            previous_value: self.take(),
+            previous_len: self.push(value),
         };
-        self.set(Some(value));
         let fun_result = fun();
         cleanup_on_drop.cleanup();
         fun_result
     }
 
-    /// Runs the given function with the value from the scope (if there's any). If you're not
-    /// inside a scope, there won't be a value (function will receive `None`).
+    /// Run the given function scoped with given, exclusive, value reference.
+    ///
+    /// This is the exclusive-access counterpart to [`scoped`]: it allows a `&mut T` to be
+    /// handed out (via [`with_mut`]) to code that's called from within `fun`.
+    ///
+    /// Note: Panicking within the function should be ok, the scope is cleaned up properly.
+    ///
+    /// [`scoped`]: #method.scoped
+    /// [`with_mut`]: #method.with_mut
+    #[inline]
+    pub fn scoped_mut<TFn, TRet>(&self, value: &mut T, fun: TFn) -> TRet
+    where
+        TFn: FnOnce() -> TRet,
+    {
+        // make sure we always remove the value (even when panicking).
+        let mut cleanup_on_drop = CleanupOnDropMut {
+            scope: Some(self),
+            previous_value: self.take_mut_ptr(),
+        };
+        self.set_mut_ptr(Some(value as *mut T));
+        let fun_result = fun();
+        cleanup_on_drop.cleanup();
+        fun_result
+    }
+
+    /// Runs the given function with the topmost value from the scope (if there's any). If
+    /// you're not inside a scope, there won't be a value (function will receive `None`).
+    ///
+    /// See [`with_all`] to look at every value currently on the stack, not just the topmost
+    /// one.
+    ///
+    /// [`with_all`]: #method.with_all
     #[inline]
     pub fn with<TFn, TRet>(&self, fun: TFn) -> TRet
     where
         TFn: FnOnce(Option<&T>) -> TRet,
     {
-        let value = self.get();
+        let value = self.top();
         fun(value)
     }
 
+    /// Returns `true` if there's currently a value in scope on this thread via [`scoped`].
+    ///
+    /// This mirrors [`with`]/[`try_with`]/[`with_all`] and only looks at the shared stack: a
+    /// [`scoped_mut`] scope does not make this return `true`, the same way it doesn't make
+    /// `with`/`try_with`/`with_all` see a value. Use [`with_mut`] to check for an exclusive
+    /// scope instead.
+    ///
+    /// [`scoped`]: #method.scoped
+    /// [`with`]: #method.with
+    /// [`try_with`]: #method.try_with
+    /// [`with_all`]: #method.with_all
+    /// [`scoped_mut`]: #method.scoped_mut
+    /// [`with_mut`]: #method.with_mut
     #[inline]
-    fn set(&self, value: Option<&T>) {
-        *self.0.borrow_mut() = if let Some(value) = value {
-            Some(value as *const T)
-        } else {
-            None
-        };
+    pub fn is_set(&self) -> bool {
+        !self.0.borrow().is_empty()
     }
 
+    /// Like [`with`], but runs the function only if there's a value currently in scope, and
+    /// returns a [`NotInScope`] error instead of calling it with `None`.
+    ///
+    /// [`with`]: #method.with
+    /// [`NotInScope`]: struct.NotInScope.html
     #[inline]
-    fn get(&self) -> Option<&T> {
-        let self_borrowed = self.0.borrow();
-        if let Some(value) = self_borrowed.deref() {
-            Some(unsafe { &*(*value) })
-        } else {
-            None
+    pub fn try_with<TFn, TRet>(&self, fun: TFn) -> Result<TRet, NotInScope>
+    where
+        TFn: FnOnce(&T) -> TRet,
+    {
+        match self.top() {
+            Some(value) => Ok(fun(value)),
+            None => Err(NotInScope::new::<T>()),
         }
     }
 
+    /// Runs the given function with every reference currently in scope on this thread,
+    /// innermost (most recently entered) first, outermost last. Yields nothing if called
+    /// outside of any scope.
+    ///
+    /// Like [`with`], the stack is only borrowed to snapshot the pointers currently on it; the
+    /// borrow is released before `fun` runs. So, just like `with`, `fun` may freely establish a
+    /// new scope on the same key (`scoped` or `scoped_many!`), or call `with_all` again - a new
+    /// scope just won't be reflected in the entries already handed to `fun`.
+    ///
+    /// [`with`]: #method.with
     #[inline]
-    fn take(&self) -> Option<&T> {
-        let mut self_borrowed = self.0.borrow_mut();
-        if let Some(taken) = self_borrowed.take() {
-            Some(unsafe { &*taken })
-        } else {
-            None
+    pub fn with_all<TFn, TRet>(&self, fun: TFn) -> TRet
+    where
+        TFn: FnOnce(&mut dyn Iterator<Item = &T>) -> TRet,
+    {
+        let snapshot: Vec<*const T> = self.0.borrow().clone();
+        let mut iter = snapshot.iter().rev().map(|&ptr| unsafe { &*ptr });
+        fun(&mut iter)
+    }
+
+    /// Runs the given function with an exclusive reference to the value from the scope (if
+    /// there's any). If you're not inside a [`scoped_mut`] scope, or a `&mut T` from this scope
+    /// is already handed out (re-entrant `with_mut`), the function receives `None` instead of a
+    /// second, aliasing reference.
+    ///
+    /// [`scoped_mut`]: #method.scoped_mut
+    #[inline]
+    pub fn with_mut<TFn, TRet>(&self, fun: TFn) -> TRet
+    where
+        TFn: FnOnce(Option<&mut T>) -> TRet,
+    {
+        let ptr = *self.1.borrow();
+        match ptr {
+            Some(ptr) if !self.2.get() => {
+                self.2.set(true);
+                let mut cleanup_on_drop = ClearMutFlagOnDrop { scope: Some(self) };
+                let fun_result = fun(Some(unsafe { &mut *ptr }));
+                cleanup_on_drop.cleanup();
+                fun_result
+            }
+            _ => fun(None),
         }
     }
+
+    /// Pushes `value` on top of the stack, returning the stack's length before the push (the
+    /// length [`truncate`] has to be called with to pop exactly this entry again).
+    ///
+    /// [`truncate`]: #method.truncate
+    #[inline]
+    fn push(&self, value: &T) -> usize {
+        let mut stack = self.0.borrow_mut();
+        let previous_len = stack.len();
+        stack.push(value as *const T);
+        previous_len
+    }
+
+    /// Truncates the stack back to `len`, popping everything pushed after it.
+    #[inline]
+    fn truncate(&self, len: usize) {
+        self.0.borrow_mut().truncate(len);
+    }
+
+    #[inline]
+    fn top(&self) -> Option<&T> {
+        let stack = self.0.borrow();
+        stack.last().map(|&ptr| unsafe { &*ptr })
+    }
+
+    #[inline]
+    fn set_mut_ptr(&self, value: Option<*mut T>) {
+        *self.1.borrow_mut() = value;
+    }
+
+    #[inline]
+    fn take_mut_ptr(&self) -> Option<*mut T> {
+        self.1.borrow_mut().take()
+    }
 }
 
 struct CleanupOnDrop<'a, T>
@@ -99,7 +230,7 @@ where
     T: ?Sized,
 {
     scope: Option<&'a Scope<T>>,
-    previous_value: Option<&'a T>,
+    previous_len: usize,
 }
 
 impl<'a, T> CleanupOnDrop<'a, T>
@@ -108,7 +239,7 @@ where
 {
     fn cleanup(&mut self) {
         if let Some(scope) = self.scope.take() {
-            scope.set(self.previous_value);
+            scope.truncate(self.previous_len);
         }
     }
 }
@@ -121,3 +252,60 @@ where
         self.cleanup();
     }
 }
+
+struct CleanupOnDropMut<'a, T>
+where
+    T: ?Sized,
+{
+    scope: Option<&'a Scope<T>>,
+    previous_value: Option<*mut T>,
+}
+
+impl<'a, T> CleanupOnDropMut<'a, T>
+where
+    T: ?Sized,
+{
+    fn cleanup(&mut self) {
+        if let Some(scope) = self.scope.take() {
+            scope.set_mut_ptr(self.previous_value);
+        }
+    }
+}
+
+impl<'a, T> Drop for CleanupOnDropMut<'a, T>
+where
+    T: ?Sized,
+{
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+/// Clears the "currently handed out mutably" flag, even if the closure passed to `with_mut`
+/// panics.
+struct ClearMutFlagOnDrop<'a, T>
+where
+    T: ?Sized,
+{
+    scope: Option<&'a Scope<T>>,
+}
+
+impl<'a, T> ClearMutFlagOnDrop<'a, T>
+where
+    T: ?Sized,
+{
+    fn cleanup(&mut self) {
+        if let Some(scope) = self.scope.take() {
+            scope.2.set(false);
+        }
+    }
+}
+
+impl<'a, T> Drop for ClearMutFlagOnDrop<'a, T>
+where
+    T: ?Sized,
+{
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
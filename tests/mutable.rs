@@ -0,0 +1,66 @@
+use thread_scoped_ref::{scoped_mut, thread_scoped_ref, with_mut};
+
+pub struct Counter(u32);
+
+thread_scoped_ref!(COUNTER, Counter);
+
+#[test]
+pub fn with_mut_modifies_the_scoped_value() {
+    let mut counter = Counter(0);
+    scoped_mut(&COUNTER, &mut counter, || {
+        with_mut(&COUNTER, |maybe_counter| {
+            maybe_counter.unwrap().0 += 1;
+        });
+        with_mut(&COUNTER, |maybe_counter| {
+            maybe_counter.unwrap().0 += 1;
+        });
+    });
+    assert_eq!(counter.0, 2);
+}
+
+#[test]
+pub fn no_scope_no_value() {
+    let found = with_mut(&COUNTER, |maybe_counter| maybe_counter.is_some());
+    assert_eq!(false, found);
+}
+
+/// Nested `scoped_mut` calls restore the previous value, just like nested `scoped` calls.
+#[test]
+pub fn nested_scopes_restore_previous_value() {
+    let mut outer = Counter(1);
+    let mut inner = Counter(2);
+
+    scoped_mut(&COUNTER, &mut outer, || {
+        with_mut(&COUNTER, |maybe_counter| {
+            assert_eq!(1, maybe_counter.unwrap().0);
+        });
+
+        scoped_mut(&COUNTER, &mut inner, || {
+            with_mut(&COUNTER, |maybe_counter| {
+                assert_eq!(2, maybe_counter.unwrap().0);
+            });
+        });
+
+        with_mut(&COUNTER, |maybe_counter| {
+            assert_eq!(1, maybe_counter.unwrap().0);
+        });
+    });
+}
+
+/// Re-entrant `with_mut` on the same key must not hand out a second, aliasing `&mut` reference.
+#[test]
+pub fn reentrant_with_mut_returns_none() {
+    let mut counter = Counter(0);
+    scoped_mut(&COUNTER, &mut counter, || {
+        with_mut(&COUNTER, |outer| {
+            let outer = outer.unwrap();
+            outer.0 += 1;
+
+            let inner_saw_none = with_mut(&COUNTER, |inner| inner.is_none());
+            assert!(inner_saw_none);
+
+            outer.0 += 1;
+        });
+    });
+    assert_eq!(counter.0, 2);
+}
@@ -0,0 +1,53 @@
+use std::panic;
+use thread_scoped_ref::{scoped_many, thread_scoped_ref, with};
+
+thread_scoped_ref!(CONFIG_VALUE, str);
+thread_scoped_ref!(USER_NAME, str);
+thread_scoped_ref!(REQUEST_ID, u64);
+
+#[test]
+pub fn scoped_many_establishes_every_key() {
+    let config_value = "production".to_string();
+    let user_name = "Ada".to_string();
+
+    scoped_many!((&CONFIG_VALUE, &config_value), (&USER_NAME, &user_name); || {
+        with(&CONFIG_VALUE, |maybe_value| {
+            assert_eq!("production", maybe_value.unwrap());
+        });
+        with(&USER_NAME, |maybe_value| {
+            assert_eq!("Ada", maybe_value.unwrap());
+        });
+    });
+
+    // all keys are restored once the body returns.
+    with(&CONFIG_VALUE, |maybe_value| assert!(maybe_value.is_none()));
+    with(&USER_NAME, |maybe_value| assert!(maybe_value.is_none()));
+}
+
+#[test]
+pub fn scoped_many_works_with_a_single_pair() {
+    let request_id = 42u64;
+
+    scoped_many!((&REQUEST_ID, &request_id); || {
+        with(&REQUEST_ID, |maybe_value| {
+            assert_eq!(42, *maybe_value.unwrap());
+        });
+    });
+}
+
+/// All keys must be restored even if the body panics partway through.
+#[test]
+pub fn scoped_many_restores_on_panic() {
+    let config_value = "staging".to_string();
+    let user_name = "Grace".to_string();
+
+    let result = panic::catch_unwind(|| {
+        scoped_many!((&CONFIG_VALUE, &config_value), (&USER_NAME, &user_name); || {
+            panic!("shit happens!");
+        });
+    });
+
+    assert!(result.is_err());
+    with(&CONFIG_VALUE, |maybe_value| assert!(maybe_value.is_none()));
+    with(&USER_NAME, |maybe_value| assert!(maybe_value.is_none()));
+}
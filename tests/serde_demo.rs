@@ -1,6 +1,6 @@
 use serde::{de, Deserialize, Deserializer};
 use std::sync::atomic::{AtomicU64, Ordering};
-use thread_scoped_ref::{scoped, thread_scoped_ref, with};
+use thread_scoped_ref::{scoped, thread_scoped_ref, try_with};
 
 thread_scoped_ref!(CURRENT_CONTEXT, dyn Context);
 
@@ -25,21 +25,9 @@ impl<'de> Deserialize<'de> for HandleFromContext {
     {
         // here we don't read any data from `deserializer`... instead we take the handles
         // from the context.
-        with(&CURRENT_CONTEXT, |maybe_context| {
-            if let Some(context) = maybe_context {
-                let maybe_handle = context.next_handle();
-                if let Some(handle) = maybe_handle {
-                    Ok(handle)
-                } else {
-                    Err(de::Error::custom("No more handles."))
-                }
-            } else {
-                Err(de::Error::custom(
-                    "Cannot deserialize HandleFromContext when \
-                there's no context in scope.",
-                ))
-            }
-        })
+        let maybe_handle = try_with(&CURRENT_CONTEXT, |context| context.next_handle())
+            .map_err(de::Error::custom)?;
+        maybe_handle.ok_or_else(|| de::Error::custom("No more handles."))
     }
 }
 
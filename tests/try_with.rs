@@ -0,0 +1,43 @@
+use thread_scoped_ref::{is_set, scoped, scoped_mut, thread_scoped_ref, try_with};
+
+thread_scoped_ref!(A_NUMBER, i32);
+
+#[test]
+pub fn is_set_reflects_whether_a_scope_is_active() {
+    assert_eq!(false, is_set(&A_NUMBER));
+
+    let value = 7;
+    scoped(&A_NUMBER, &value, || {
+        assert_eq!(true, is_set(&A_NUMBER));
+    });
+
+    assert_eq!(false, is_set(&A_NUMBER));
+}
+
+/// `is_set` only reflects the shared stack, same as `with`/`try_with`/`with_all`: a
+/// `scoped_mut` scope does not make it return `true`.
+#[test]
+pub fn is_set_does_not_reflect_an_active_scoped_mut() {
+    assert_eq!(false, is_set(&A_NUMBER));
+
+    let mut value = 7;
+    scoped_mut(&A_NUMBER, &mut value, || {
+        assert_eq!(false, is_set(&A_NUMBER));
+    });
+
+    assert_eq!(false, is_set(&A_NUMBER));
+}
+
+#[test]
+pub fn try_with_runs_the_closure_when_in_scope() {
+    let value = 7;
+    let result = scoped(&A_NUMBER, &value, || try_with(&A_NUMBER, |n| *n * 2));
+    assert_eq!(14, result.unwrap());
+}
+
+#[test]
+pub fn try_with_returns_not_in_scope_outside_a_scope() {
+    let result = try_with(&A_NUMBER, |n| *n);
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("i32"));
+}
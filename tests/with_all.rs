@@ -0,0 +1,55 @@
+use thread_scoped_ref::{scoped, thread_scoped_ref, with_all};
+
+pub struct Provider(&'static str);
+
+thread_scoped_ref!(PROVIDER, Provider);
+
+#[test]
+pub fn with_all_yields_nothing_outside_a_scope() {
+    let names = with_all(&PROVIDER, |providers| providers.map(|p| p.0).collect::<Vec<_>>());
+    assert!(names.is_empty());
+}
+
+#[test]
+pub fn with_all_walks_the_stack_innermost_first() {
+    let outer = Provider("outer");
+    let middle = Provider("middle");
+    let inner = Provider("inner");
+
+    scoped(&PROVIDER, &outer, || {
+        scoped(&PROVIDER, &middle, || {
+            scoped(&PROVIDER, &inner, || {
+                let names =
+                    with_all(&PROVIDER, |providers| providers.map(|p| p.0).collect::<Vec<_>>());
+                assert_eq!(vec!["inner", "middle", "outer"], names);
+            });
+
+            // after the inner scope ends, it's dropped from the stack.
+            let names =
+                with_all(&PROVIDER, |providers| providers.map(|p| p.0).collect::<Vec<_>>());
+            assert_eq!(vec!["middle", "outer"], names);
+        });
+    });
+}
+
+/// `with_all` only borrows the stack long enough to snapshot it, so establishing a new scope
+/// for the same key from within its closure works fine, just like it does with `with`; it's
+/// simply not reflected in the entries `with_all` already handed to the closure.
+#[test]
+pub fn with_all_supports_nesting_a_new_scope() {
+    let outer = Provider("outer");
+    let inner = Provider("inner");
+
+    scoped(&PROVIDER, &outer, || {
+        with_all(&PROVIDER, |providers| {
+            let names_before = providers.map(|p| p.0).collect::<Vec<_>>();
+            assert_eq!(vec!["outer"], names_before);
+
+            scoped(&PROVIDER, &inner, || {
+                let names_inside =
+                    with_all(&PROVIDER, |providers| providers.map(|p| p.0).collect::<Vec<_>>());
+                assert_eq!(vec!["inner", "outer"], names_inside);
+            });
+        });
+    });
+}